@@ -16,6 +16,219 @@ pub trait GuessMethod {
 pub struct MostCommonMethod{ pub common: u8 }
 pub struct KeyEliminationMethod<'a>{ pub crib: &'a [u8] }
 
+/// Recovers each key byte by scoring a whole column against an English
+/// character-frequency model, rather than betting on a single most-common
+/// byte. This is more robust than [`MostCommonMethod`] on plaintext that
+/// isn't space-dominated (code, heavily-punctuated prose, uppercase text).
+pub struct FrequencyScoreMethod {
+    /// How many lowest-scoring (most English-like) candidate bytes to keep
+    /// per column. The final key guesses are the cartesian product of these
+    /// per-column candidates, capped at [`MAX_KEY_COMBINATIONS`].
+    pub top_n: usize,
+}
+
+/// Number of letter categories (a-z, folded case-insensitively).
+const LETTER_COUNT: usize = 26;
+/// Letters, plus space, plus "other printable", plus "non-printable".
+const CATEGORY_COUNT: usize = LETTER_COUNT + 3;
+const SPACE_CATEGORY: usize = LETTER_COUNT;
+const OTHER_PRINTABLE_CATEGORY: usize = LETTER_COUNT + 1;
+const NON_PRINTABLE_CATEGORY: usize = LETTER_COUNT + 2;
+
+/// Extra penalty added per non-printable byte, on top of its (small)
+/// contribution to the chi-squared statistic. This strongly discourages
+/// candidate keys that decrypt to binary garbage.
+const NON_PRINTABLE_PENALTY: f32 = 1_000.0;
+
+/// How many full key guesses to emit at most. The per-column candidates are
+/// combined cartesian-style, so this bounds the combinatorial blow-up when
+/// `top_n` and `key_length` are both large.
+const MAX_KEY_COMBINATIONS: usize = 100;
+
+/// Relative frequencies of a-z, space, other printable characters and
+/// non-printable characters in English text, used as the expected
+/// distribution for the chi-squared test in [`FrequencyScoreMethod`].
+///
+/// The letter frequencies are Wikipedia's "Letter frequency" table, which is
+/// a distribution over letters alone (it sums to ~1 on its own). It's
+/// rescaled by `1 - space - other_printable - non_printable` here so the
+/// full 29-entry table is a valid probability distribution that also
+/// accounts for non-letter characters.
+#[rustfmt::skip]
+const ENGLISH_FREQUENCIES: [f32; CATEGORY_COUNT] = [
+    0.065704, 0.012003, 0.022381, 0.034215, 0.102188, 0.017924, 0.016211, 0.049026, // a-h
+    0.056041, 0.001231, 0.006211, 0.032381, 0.019356, 0.054296, 0.060394, 0.015519, // i-p
+    0.000764, 0.048165, 0.050901, 0.072856, 0.022188, 0.007868, 0.018986, 0.001207, // q-x
+    0.015881, 0.000595, // y-z
+    0.17000, // space
+    0.02500, // other printable
+    0.00050, // non-printable
+];
+
+fn byte_category(byte: u8) -> usize {
+    match byte {
+        b'a'..=b'z' => (byte - b'a') as usize,
+        b'A'..=b'Z' => (byte - b'A') as usize,
+        b' ' => SPACE_CATEGORY,
+        0x21..=0x7e => OTHER_PRINTABLE_CATEGORY,
+        _ => NON_PRINTABLE_CATEGORY,
+    }
+}
+
+/// Scores how English-like a stream of bytes is via a chi-squared test
+/// against [`ENGLISH_FREQUENCIES`]. Lower scores are more English-like.
+fn chi_squared_score(bytes: impl Iterator<Item = u8>) -> f32 {
+    let mut counts = [0usize; CATEGORY_COUNT];
+    let mut len = 0usize;
+    for byte in bytes {
+        counts[byte_category(byte)] += 1;
+        len += 1;
+    }
+
+    if len == 0 {
+        return f32::MAX;
+    }
+
+    let mut score = 0.0;
+    for (i, &expected) in ENGLISH_FREQUENCIES.iter().enumerate() {
+        let observed = counts[i] as f32 / len as f32;
+        score += (observed - expected).powi(2) / expected;
+    }
+
+    score += counts[NON_PRINTABLE_CATEGORY] as f32 * NON_PRINTABLE_PENALTY;
+    score
+}
+
+/// Scores how English-like `data` is, for ranking whole decrypted plaintext
+/// candidates. Higher scores are more English-like (the inverse of
+/// [`chi_squared_score`], where lower is better), so candidates can be
+/// sorted descending by this score.
+pub fn score_plaintext(data: &[u8]) -> f32 {
+    -chi_squared_score(data.iter().copied())
+}
+
+/// One candidate combination under construction: `indices[i]` selects which
+/// of `columns[i]`'s ranked candidates is currently chosen, and `score` is
+/// the sum of the chosen candidates' per-column scores (lower is better).
+struct RankedCombo {
+    score: f32,
+    indices: Vec<usize>,
+}
+
+impl PartialEq for RankedCombo {
+    fn eq(&self, other: &Self) -> bool {
+        self.score == other.score
+    }
+}
+impl Eq for RankedCombo {}
+impl PartialOrd for RankedCombo {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for RankedCombo {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Reversed so `BinaryHeap` (a max-heap) pops the lowest score first.
+        other.score.partial_cmp(&self.score).unwrap_or(std::cmp::Ordering::Equal)
+    }
+}
+
+/// Combines per-column ranked candidates `columns[i] = [(score, byte), ...]`
+/// (best candidate first) into full key guesses, returning the `limit`
+/// lowest-total-score combinations in ascending score order.
+///
+/// Unlike a naive left-to-right cartesian product, which exhausts `limit`
+/// on the first few columns and leaves every later column frozen to its
+/// best candidate, this lazily expands a priority queue of partial
+/// combinations ordered by summed score, so a deviation in any column -
+/// early or late - can surface among the top `limit` results.
+fn cartesian_product(columns: &[Vec<(f32, u8)>], limit: usize) -> Vec<Vec<u8>> {
+    use std::collections::{BinaryHeap, HashSet};
+
+    if limit == 0 || columns.is_empty() || columns.iter().any(Vec::is_empty) {
+        return Vec::new();
+    }
+
+    let score_of = |indices: &[usize]| -> f32 {
+        indices.iter().zip(columns).map(|(&i, col)| col[i].0).sum()
+    };
+
+    let start = vec![0usize; columns.len()];
+    let mut seen: HashSet<Vec<usize>> = HashSet::new();
+    seen.insert(start.clone());
+    let mut queue = BinaryHeap::new();
+    queue.push(RankedCombo { score: score_of(&start), indices: start });
+
+    let mut combos = Vec::with_capacity(limit);
+    while combos.len() < limit {
+        let Some(RankedCombo { indices, .. }) = queue.pop() else {
+            break;
+        };
+
+        combos.push(indices.iter().zip(columns).map(|(&i, col)| col[i].1).collect());
+
+        for (col, next_indices) in columns.iter().enumerate() {
+            let mut next = indices.clone();
+            next[col] += 1;
+            if next[col] >= next_indices.len() || !seen.insert(next.clone()) {
+                continue;
+            }
+            queue.push(RankedCombo { score: score_of(&next), indices: next });
+        }
+    }
+
+    combos
+}
+
+impl GuessMethod for FrequencyScoreMethod {
+    fn is_valid(&self, data: &[u8], context: &Context) -> Result<(), Box<dyn Error>> {
+        if context.key_length == 0 {
+            return Err(simple_error!("Key length must be greater than 0"))?;
+        }
+
+        // Every key-byte position needs at least one sample byte to score,
+        // otherwise that column's candidates would all score f32::MAX and
+        // its recovered key byte would be an arbitrary pick.
+        if data.len() < context.key_length {
+            return Err(simple_error!(
+                "The data must be at least as long as the key length"
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn guess_key(&self, data: &[u8], context: &mut Context) -> Vec<ArrVec<u8>> {
+        let top_n = self.top_n.max(1);
+        let mut per_column_candidates: Vec<Vec<(f32, u8)>> = Vec::with_capacity(context.key_length);
+
+        for i in 0..context.key_length {
+            let column: Vec<u8> = data.iter().skip(i).step_by(context.key_length).copied().collect();
+
+            let mut scored: Vec<(f32, u8)> = (0..=255u8)
+                .map(|candidate| {
+                    let score = chi_squared_score(column.iter().map(|&b| b ^ candidate));
+                    (score, candidate)
+                })
+                .collect();
+            scored.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            debug!(
+                "Column {}: best candidate {:#x} (score {})",
+                i, scored[0].1, scored[0].0
+            );
+
+            scored.truncate(top_n);
+            per_column_candidates.push(scored);
+        }
+
+        cartesian_product(&per_column_candidates, MAX_KEY_COMBINATIONS)
+            .into_iter()
+            .map(|key| key.into_iter().collect::<ArrVec<u8>>())
+            .collect()
+    }
+}
+
 impl<'a> GuessMethod for MostCommonMethod {
     // Checks if the guessing method is valid
     // for a certain key length
@@ -120,3 +333,108 @@ pub fn guess_key(
     method.is_valid(data, context)?;
     Ok(method.guess_key(data, context))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chi_squared_score_prefers_english_like_text() {
+        let english = b"the quick brown fox jumps over the lazy dog";
+        let garbage: Vec<u8> = (0u8..=44).collect();
+
+        assert!(chi_squared_score(english.iter().copied()) < chi_squared_score(garbage.iter().copied()));
+    }
+
+    #[test]
+    fn chi_squared_score_penalises_non_printable_bytes() {
+        let printable = b"aaaaaaaaaa";
+        let mut with_control = *printable;
+        with_control[0] = 0x01;
+
+        assert!(
+            chi_squared_score(with_control.iter().copied()) > chi_squared_score(printable.iter().copied())
+        );
+    }
+
+    #[test]
+    fn chi_squared_score_of_empty_column_is_max() {
+        assert_eq!(chi_squared_score(std::iter::empty()), f32::MAX);
+    }
+
+    #[test]
+    fn cartesian_product_combines_all_columns() {
+        let columns = vec![
+            vec![(0.0, 1), (1.0, 2)],
+            vec![(0.0, 3), (1.0, 4)],
+        ];
+        let mut combos = cartesian_product(&columns, 100);
+        combos.sort();
+
+        assert_eq!(combos, vec![vec![1, 3], vec![1, 4], vec![2, 3], vec![2, 4]]);
+    }
+
+    #[test]
+    fn cartesian_product_respects_the_limit() {
+        let columns = vec![
+            vec![(0.0, 1), (1.0, 2), (2.0, 3)],
+            vec![(0.0, 1), (1.0, 2), (2.0, 3)],
+            vec![(0.0, 1), (1.0, 2), (2.0, 3)],
+        ];
+        let combos = cartesian_product(&columns, 5);
+
+        assert_eq!(combos.len(), 5);
+    }
+
+    #[test]
+    fn cartesian_product_can_surface_a_deviation_in_an_early_column() {
+        // 10 columns, each with the best candidate (score 0.0) much cheaper
+        // than its alternative (5.0), except column 4, where the "true" byte
+        // only ranks second at score 0.1. That combo is one swap away from
+        // the globally-best combo and has the second-lowest total score, so
+        // it must be found well within a 100-combination budget.
+        let columns: Vec<Vec<(f32, u8)>> = (0..10)
+            .map(|i| {
+                if i == 4 {
+                    vec![(0.0, 0), (0.1, 99)]
+                } else {
+                    vec![(0.0, 0), (5.0, 1)]
+                }
+            })
+            .collect();
+
+        let combos = cartesian_product(&columns, MAX_KEY_COMBINATIONS);
+        let mut expected = vec![0u8; 10];
+        expected[4] = 99;
+
+        assert!(combos.contains(&expected));
+    }
+
+    #[test]
+    fn frequency_score_method_rejects_data_shorter_than_key_length() {
+        let method = FrequencyScoreMethod { top_n: 3 };
+        let context = Context::new(4);
+
+        assert!(method.is_valid(b"abc", &context).is_err());
+        assert!(method.is_valid(b"abcd", &context).is_ok());
+    }
+
+    #[test]
+    fn frequency_score_method_recovers_a_known_key_over_english_text() {
+        let key: &[u8] = b"beef";
+        let plaintext: &[u8] = b"the quick brown fox jumps over the lazy dog and everyone loved \
+            the story since it was delightful and inspiring to read aloud at night under the \
+            stars while thinking about the future of science and art";
+        let data: Vec<u8> = plaintext
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| b ^ key[i % key.len()])
+            .collect();
+
+        let method = FrequencyScoreMethod { top_n: 3 };
+        let mut context = Context::new(key.len());
+        let guesses = method.guess_key(&data, &mut context);
+
+        assert_eq!(&guesses[0][..], key);
+    }
+}
@@ -28,25 +28,21 @@ fn calc_ic_for_key_length(data: &[u8], key_length: NonZeroUsize) -> f32 {
     mean / (key_length.get() as f32)
 }
 
-pub fn analyse_key_length(data: &[u8], max_length: usize, target_ic: f32) -> usize {
-    let mut ic_vals = Vec::new();
-    let max_length = usize::min(max_length, data.len());
-    ic_vals.resize(max_length, f32::MAX);
-
-    for i in 1..=max_length {
-        let length = NonZeroUsize::new(i).unwrap();
-        let ic = calc_ic_for_key_length(data, length);
-        ic_vals[i - 1] = ic;
-    }
-
+// Selects the best key length (1-based) from a list of per-length scores,
+// where a lower score indicates a better candidate (e.g. distance from a
+// target IC, or average normalised Hamming distance). If two scores are
+// within 0.001 of each other and the better length is a whole multiple of
+// the other, the shorter length is kept instead, since repeating sub-keys
+// otherwise get reported as a multiple of the true key length.
+fn select_best_length(scores: &[f32], metric_name: &str) -> usize {
     let mut best_guess_i = 0;
-    for i in 0..ic_vals.len() {
-        let diff = (ic_vals[i] - target_ic).abs();
-        let best_diff = (ic_vals[best_guess_i] - target_ic).abs();
+    for i in 0..scores.len() {
+        let diff = scores[i];
+        let best_diff = scores[best_guess_i];
 
         let mut is_multiple = false;
 
-        // If the ic values are approx the same
+        // If the scores are approx the same
         // we check if current key length is a multiple
         // of the previous best. If it is we ignore it
         // otherwise we choose the longer key length
@@ -67,15 +63,163 @@ pub fn analyse_key_length(data: &[u8], max_length: usize, target_ic: f32) -> usi
 
         if is_multiple {
             debug!(
-                "Key Length: {}, IC: {} {}",
+                "Key Length: {}, {}: {} {}",
                 i + 1,
-                ic_vals[i],
+                metric_name,
+                scores[i],
                 style(format!("IGNORED: Multiple of {}", best_guess_i + 1)).red()
             );
         } else {
-            debug!("Key Length: {}, IC: {}", i + 1, ic_vals[i]);
+            debug!("Key Length: {}, {}: {}", i + 1, metric_name, scores[i]);
         }
     }
 
     best_guess_i + 1
 }
+
+pub fn analyse_key_length(data: &[u8], max_length: usize, target_ic: f32) -> usize {
+    let max_length = usize::min(max_length, data.len());
+    let diffs: Vec<f32> = (1..=max_length)
+        .map(|i| {
+            let length = NonZeroUsize::new(i).unwrap();
+            (calc_ic_for_key_length(data, length) - target_ic).abs()
+        })
+        .collect();
+
+    select_best_length(&diffs, "IC")
+}
+
+// calc_hamming_distance returns the popcount of the bitwise XOR of two
+// equal-length byte slices, i.e. the number of differing bits.
+fn calc_hamming_distance(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+fn calc_normalised_hamming_for_key_length(data: &[u8], key_length: NonZeroUsize) -> f32 {
+    let key_length = key_length.get();
+
+    // Average over every consecutive block pair the data provides, not just
+    // the first few: with only a handful of blocks sampled, noise in any one
+    // pair can outweigh the signal and pick the wrong length (on the English
+    // text used in this module's tests, sampling only the first 5 blocks
+    // picks length 9 over the true length-4 key).
+    let blocks: Vec<&[u8]> = data.chunks_exact(key_length).collect();
+
+    // Need at least two full blocks to compare.
+    if blocks.len() < 2 {
+        return f32::MAX;
+    }
+
+    let mut total = 0.0f32;
+    let mut pairs = 0usize;
+    for pair in blocks.windows(2) {
+        total += calc_hamming_distance(pair[0], pair[1]) as f32 / key_length as f32;
+        pairs += 1;
+    }
+
+    total / pairs as f32
+}
+
+/// Estimates the key length by finding the length whose consecutive blocks
+/// have the smallest average (length-normalised) Hamming distance. Unlike
+/// [`analyse_key_length`], this doesn't assume a language-like byte
+/// distribution, so it works better on binary or compressed plaintext.
+pub fn analyse_key_length_hamming(data: &[u8], max_length: usize) -> usize {
+    let max_length = usize::min(max_length, data.len());
+    let scores: Vec<f32> = (1..=max_length)
+        .map(|i| {
+            let length = NonZeroUsize::new(i).unwrap();
+            calc_normalised_hamming_for_key_length(data, length)
+        })
+        .collect();
+
+    select_best_length(&scores, "Hamming")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calc_hamming_distance_counts_differing_bits() {
+        assert_eq!(calc_hamming_distance(b"this is a test", b"wokka wokka!!!"), 37);
+    }
+
+    #[test]
+    fn calc_hamming_distance_of_identical_slices_is_zero() {
+        assert_eq!(calc_hamming_distance(b"abcd", b"abcd"), 0);
+    }
+
+    #[test]
+    fn calc_normalised_hamming_requires_two_full_blocks() {
+        let length = NonZeroUsize::new(4).unwrap();
+        assert_eq!(calc_normalised_hamming_for_key_length(b"abc", length), f32::MAX);
+        assert_eq!(calc_normalised_hamming_for_key_length(b"abcd", length), f32::MAX);
+    }
+
+    #[test]
+    fn calc_normalised_hamming_is_zero_for_a_repeating_block() {
+        let length = NonZeroUsize::new(4).unwrap();
+        assert_eq!(calc_normalised_hamming_for_key_length(b"abcdabcdabcd", length), 0.0);
+    }
+
+    #[test]
+    fn analyse_key_length_hamming_finds_the_repeating_period() {
+        // A plaintext with a repeating 4-byte period gives perfectly
+        // identical ciphertext blocks (Hamming distance 0) whenever the
+        // candidate key length also lines up on that period.
+        let key = b"beef";
+        let plaintext = b"abcd".repeat(8);
+        let data: Vec<u8> = plaintext
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| b ^ key[i % key.len()])
+            .collect();
+
+        assert_eq!(analyse_key_length_hamming(&data, 16), key.len());
+    }
+
+    #[test]
+    fn analyse_key_length_hamming_finds_the_key_length_over_noisy_english_text() {
+        // Unlike the repeating-period test above, real English text doesn't
+        // give identical blocks at the true key length, just a *lower
+        // average* Hamming distance than other lengths, and that signal
+        // only emerges once enough block pairs are averaged together -
+        // sampling only the first few blocks is noisy enough to pick a
+        // wrong candidate length.
+        let key = b"beef";
+        let plaintext: &[u8] = b"A gentle breeze carried the scent of rain across the quiet \
+            meadow. Travelers crossed the desert guided only by the stars and old maps. The \
+            committee debated the proposal for \
+            nearly three hours before voting. Engineers spent years refining the design before it \
+            finally worked well. The bakery down the street always smelled of fresh bread each \
+            morning. Many readers find comfort in the steady rhythm of a well told story. Music has a \
+            way of bringing people together regardless of language. A curious cat watched the birds \
+            from its perch on the windowsill. The mountain peak was covered in snow even during the \
+            warmest months. Every morning she walked along the shore collecting shells and driftwood. \
+            The quick brown fox jumps over the lazy dog near the riverbank at dawn. History often \
+            repeats itself in ways that surprise even careful observers. Children laughed as they \
+            chased one another through the autumn leaves. Scientists have long debated the origins of \
+            language and its slow evolution. The old library held thousands of books stacked high \
+            against the walls. A gentle breeze carried the scent of rain across the quiet meadow. \
+            Travelers crossed the desert guided only by the stars and old maps. The committee debated \
+            the proposal for nearly three hours before voting. Engineers spent years refining the \
+            design before it finally worked well. The bakery down the street always smelled of fresh \
+            bread each morning. Many readers find comfort in the steady rhythm of a well told story. \
+            Music has a way of bringing people together regardless of language. A curious cat watched \
+            the birds from its perch on the windowsill. The mountain peak was covered in snow even \
+            during the warmest months. Every morning she walked along the shore collecting shells and \
+            driftwood. The quick brown fox jumps over the lazy dog near the riverbank at dawn. History \
+            often repeats itself in ways that surprise even careful observers. Children laughed as \
+            they chased one another through the autumn leaves. Scientists have long debated the \
+            origins of language and its slow evolution. The old library held thousands of books \
+            stacked high against the walls.";
+        let data: Vec<u8> = plaintext
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| b ^ key[i % key.len()])
+            .collect();
+
+        assert_eq!(analyse_key_length_hamming(&data, 16), key.len());
+    }
+}
@@ -16,6 +16,18 @@ use std::process;
 mod key_guess;
 mod kl_anal;
 
+#[cfg(test)]
+mod lib_test;
+
+/// The algorithm used to estimate the key length
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum KeyLengthMethod {
+    /// Index of coincidence based analysis. Best for natural-language plaintext.
+    Ic,
+    /// Hamming-distance based analysis. Best for binary or compressed plaintext.
+    Hamming,
+}
+
 #[derive(Parser, Debug)]
 #[clap(author = "Oliver W. (obwan02)", version, about, long_about = None)]
 pub struct Config {
@@ -59,6 +71,15 @@ pub struct Config {
     #[clap(short, long = "target-ic", default_value_t = 0.067, global = true)]
     target_ic: f32,
 
+    /// The algorithm used to estimate the key length
+    ///
+    /// By default key length analysis uses the index of coincidence, which
+    /// works best on natural-language plaintext. Use `hamming` when cracking
+    /// XOR over binary or compressed data, where byte distributions aren't
+    /// language-like.
+    #[clap(long = "kl-method", value_enum, default_value = "ic", global = true)]
+    kl_method: KeyLengthMethod,
+
     /// Specifies if the output should be verbose or not
     #[clap(short, long, global = true)]
     pub verbose: bool,
@@ -76,6 +97,14 @@ pub struct Config {
     /// of the program run.
     #[clap(short = 'l', long, global = true)]
     pub key_length_only: bool,
+
+    /// Disables ranking key guesses by English-likeness
+    ///
+    /// By default, candidate keys are sorted best-first by how English-like
+    /// the plaintext they decrypt to is. This flag keeps them in the raw
+    /// order the guessing method found them, e.g. for scripting.
+    #[clap(long, global = true)]
+    pub no_rank_keys: bool,
 }
 
 pub struct Context {
@@ -163,6 +192,72 @@ enum Commands {
         /// is at least 4 characters longer than the estimated key length for accurate results.
         crib: String,
     },
+
+    /// Perform chi-squared English-frequency analysis on the input data
+    ///
+    /// Where `common` looks for a single most-frequent byte per key-byte
+    /// position, this mode weighs the whole letter distribution of each
+    /// position against expected English frequencies, so it tends to cope
+    /// better with tricky plaintext like source code or mixed-case prose.
+    #[clap(name = "freq")]
+    Frequency {
+        /// The file to analyse
+        ///
+        /// Specifies the input file for xanal to analyse.
+        /// A '-' can be provided to read from stdin. If reading
+        /// from stdin, the program will output after an EOF.
+        #[clap(short = 'f')]
+        file: String,
+
+        /// How many candidate bytes to keep per key-byte position
+        ///
+        /// The final key guesses are the cartesian product of the top-n
+        /// candidates for each key-byte position, so raising this increases
+        /// the chance of finding the real key at the cost of more guesses
+        /// to sift through. Default is 3.
+        #[clap(long, default_value_t = 3)]
+        top_n: usize,
+    },
+
+    /// Apply a known repeating-key XOR key to the input data
+    ///
+    /// This is the inverse of the other subcommands: instead of guessing a
+    /// key, it applies one you already know. Since XOR is its own inverse,
+    /// it can be used to either encrypt plaintext to generate test vectors,
+    /// or to decrypt ciphertext once you've recovered the key. Note that
+    /// this subcommand ignores the key length analysis and output ranking
+    /// flags, since no key guessing takes place.
+    #[clap(name = "encrypt", alias = "apply")]
+    Encrypt {
+        /// The file to apply the key to
+        ///
+        /// Specifies the input file for xanal to read.
+        /// A '-' can be provided to read from stdin. If reading
+        /// from stdin, the program will output after an EOF.
+        #[clap(short = 'f')]
+        file: String,
+
+        /// The repeating key to XOR the input with
+        ///
+        /// Accepts a raw string key, or the same `hex:`/`base64:` prefixed
+        /// formats that xanal prints alongside a recovered key, e.g.
+        /// `hex:62656566` or `base64:YmVlZg==`.
+        #[clap(long)]
+        key: String,
+    },
+}
+
+// Parses a key argument for the encrypt subcommand, accepting a raw string
+// key or a `hex:`/`base64:` prefixed key, matching the formats xanal prints
+// alongside a recovered key.
+fn parse_key(key: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if let Some(hex_str) = key.strip_prefix("hex:") {
+        Ok(hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str))?)
+    } else if let Some(base64_str) = key.strip_prefix("base64:") {
+        Ok(base64::decode(base64_str)?)
+    } else {
+        Ok(key.as_bytes().to_vec())
+    }
 }
 
 #[cfg(test)]
@@ -187,6 +282,8 @@ fn read_input(config: &Config) -> Result<Vec<u8>, io::Error> {
     let file = match &config.command {
         Commands::MostCommon { ref file, .. } => file,
         Commands::KeyElimination { ref file, .. } => file,
+        Commands::Frequency { ref file, .. } => file,
+        Commands::Encrypt { ref file, .. } => file,
     };
 
     if file.as_str() == "-" {
@@ -220,11 +317,36 @@ pub fn run(config: Config, enable_verbose: impl FnOnce() -> ()) -> Result<(), Bo
         return Err(Box::new(simple_error!("No data was provided")));
     }
 
+    // The encrypt subcommand applies a known key directly, so it skips key
+    // length analysis, key guessing and output ranking entirely.
+    if let Commands::Encrypt { ref key, .. } = config.command {
+        let key = parse_key(key)?;
+        if key.is_empty() {
+            return Err(Box::new(simple_error!("The provided key is empty")));
+        }
+
+        let output: Vec<u8> = decrypt(&data, &key).collect();
+        return match config.output_file {
+            Some(output_file) => write_file(output_file, &output).map_err(|e| e.into()),
+            None => {
+                io::stdout().write_all(&output)?;
+                Ok(())
+            }
+        };
+    }
+
     let key_length = if let Some(x) = config.specific_key_length {
         info!("Using Key Length: {}", x);
         x
     } else {
-        let x = kl_anal::analyse_key_length(&data, config.max_key_length, 0.067);
+        let x = match config.kl_method {
+            KeyLengthMethod::Ic => {
+                kl_anal::analyse_key_length(&data, config.max_key_length, config.target_ic)
+            }
+            KeyLengthMethod::Hamming => {
+                kl_anal::analyse_key_length_hamming(&data, config.max_key_length)
+            }
+        };
 
         if x == 0 {
             return Err(Box::new(simple_error!("Guessed key length is 0")));
@@ -241,23 +363,29 @@ pub fn run(config: Config, enable_verbose: impl FnOnce() -> ()) -> Result<(), Bo
     // Establish context
     let mut context = Context::new(key_length);
 
-    let method = match &config.command {
+    let method: Box<dyn GuessMethod> = match &config.command {
         Commands::MostCommon {
             most_common_byte: x,
             ..
-        } => GuessMethod::MostCommon(x.unwrap_or(32)),
-        Commands::KeyElimination { crib, .. } => GuessMethod::KeyElimination(crib.as_bytes()),
+        } => Box::new(MostCommonMethod {
+            common: x.unwrap_or(32),
+        }),
+        Commands::KeyElimination { crib, .. } => Box::new(KeyEliminationMethod {
+            crib: crib.as_bytes(),
+        }),
+        Commands::Frequency { top_n, .. } => Box::new(FrequencyScoreMethod { top_n: *top_n }),
+        Commands::Encrypt { .. } => unreachable!("handled by the early return above"),
     };
 
     // We need to warn users about using the most common method with very few data points.
     // This is because frequency analysis isn't very effective with much data. I choose the warning
     // point as 30 characters because everybody always says 30 is a good sample size (it also is
     // probably a bare minimum in case of frequency analysis because the range of .
-    if matches!(method, GuessMethod::MostCommon(..)) && data.len() / key_length < 30 {
+    if matches!(config.command, Commands::MostCommon { .. }) && data.len() / key_length < 30 {
         warn!("The selected key length probably does not give enough data to analyse");
     }
 
-    let key_guesses = guess_key(&data, method, &mut context)?;
+    let key_guesses = guess_key(&data, method.as_ref(), &mut context)?;
 
     // The guess key function is never supposed to return 0 keys
     // (if it does it returns an Err instead). However, it never hurts to
@@ -266,12 +394,29 @@ pub fn run(config: Config, enable_verbose: impl FnOnce() -> ()) -> Result<(), Bo
         return Err(Box::new(simple_error!("No suitable keys founds")));
     }
 
-    for (i, item) in key_guesses.iter().enumerate() {
+    // Rank candidate keys best-first by how English-like the plaintext they
+    // decrypt to is, so users don't have to eyeball arbitrary match order to
+    // find the real key. This can be disabled for scripting with --no-rank-keys.
+    let mut key_guesses: Vec<(ArrVec<u8>, f32)> = key_guesses
+        .into_iter()
+        .map(|key| {
+            let decrypted: Vec<u8> = decrypt(&data, &key).collect();
+            let score = score_plaintext(&decrypted);
+            (key, score)
+        })
+        .collect();
+
+    if !config.no_rank_keys {
+        key_guesses.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+    }
+
+    for (i, (item, score)) in key_guesses.iter().enumerate() {
         let index_name = format!(" Guess #{} ", i);
         info!("{:-^36}", index_name);
         info!("Key Guess: {}", String::from_utf8_lossy(item));
-        info!("Key Guess (base64): {}", base64::encode(item));
-        info!("Key Guess (hex): 0x{}", hex::encode(item));
+        info!("Key Guess (base64): base64:{}", base64::encode(item));
+        info!("Key Guess (hex): hex:{}", hex::encode(item));
+        info!("English-likeness Score: {}", score);
     }
 
     if let Some(output_file) = config.output_file {
@@ -279,10 +424,10 @@ pub fn run(config: Config, enable_verbose: impl FnOnce() -> ()) -> Result<(), Bo
             0 => return Err(Box::new(simple_error!("No keys found"))),
             1 => write_file(
                 output_file,
-                &decrypt(&data, &key_guesses[0]).collect::<Vec<_>>(),
+                &decrypt(&data, &key_guesses[0].0).collect::<Vec<_>>(),
             )?,
             _ => {
-                for (i, key) in key_guesses.iter().enumerate() {
+                for (i, (key, _)) in key_guesses.iter().enumerate() {
                     let path = std::path::Path::new(&output_file);
                     let dot = if path.extension().is_some() { "." } else { "" };
                     let path = path.with_file_name(&format!(
@@ -11,19 +11,45 @@ fn test_decrypt_1() {
 #[should_panic]
 fn test_config_1() {
     let config = super::Config {
-        file: String::from("lknfqwefbqiernvkbaxcjhMNZXCLKj.mascl ik.SNFM"),
-        crib: None,
+        command: super::Commands::MostCommon {
+            file: String::from("lknfqwefbqiernvkbaxcjhMNZXCLKj.mascl ik.SNFM"),
+            most_common_byte: Some(0x20),
+        },
         verbose: false,
         target_ic: 0.067,
-        crib_offset: None,
-        crib_search: None,
+        kl_method: super::KeyLengthMethod::Ic,
         max_key_length: 16,
         no_color_output: true,
-        most_common_byte: 0x20,
         key_length_only: false,
         specific_key_length: None,
+        no_rank_keys: false,
         output_file: None,
     };
 
-    super::run(config, || ());
+    super::run(config, || ()).unwrap();
+}
+
+// Closes the loop with test_decrypt_1's vector: applying a known key via
+// parse_key (as the `encrypt` subcommand does) should round-trip back to
+// the original plaintext.
+#[test]
+fn test_encrypt_round_trip() {
+    let plaintext = b"Hello this is an xanal test :)";
+
+    let encrypted: Vec<u8> = super::decrypt(plaintext, b"beef").collect();
+    assert_eq!(
+        hex::encode(&encrypted),
+        "2a00090a0d45110e0b16450f11450408421d04080309451207161146584c"
+    );
+
+    let key = super::parse_key("beef").unwrap();
+    let decrypted: Vec<u8> = super::decrypt(&encrypted, &key).collect();
+    assert_eq!(&decrypted, plaintext);
+}
+
+#[test]
+fn test_parse_key_prefixes() {
+    assert_eq!(super::parse_key("beef").unwrap(), b"beef");
+    assert_eq!(super::parse_key("hex:62656566").unwrap(), b"beef");
+    assert_eq!(super::parse_key("base64:YmVlZg==").unwrap(), b"beef");
 }